@@ -0,0 +1,343 @@
+// Copyright © SurrealDB Ltd
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module abstracts the primitive store operations over a backend.
+
+use crate::err::Error;
+use crate::kv::Key;
+use crate::kv::Val;
+use std::ops::Range;
+
+/// The direction in which a range of keys is scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	/// Scan the range in ascending key order
+	Forward,
+	/// Scan the range in descending key order
+	Reverse,
+}
+
+/// The primitive store operations backing a [`Transaction`]
+///
+/// Abstracting these operations lets the transaction logic — savepoint
+/// undo, conditional writes, and range scans — run against either the
+/// browser's IndexedDB engine or an in-memory store for native testing.
+///
+/// [`Transaction`]: crate::tx::Transaction
+#[allow(async_fn_in_trait)]
+pub trait Backend {
+	/// Fetch the value stored for a key in the named store
+	async fn get(&self, store: &str, key: &[u8]) -> Result<Option<Val>, Error>;
+	/// Insert or update the value stored for a key in the named store
+	async fn put(&self, store: &str, key: &[u8], val: &[u8]) -> Result<(), Error>;
+	/// Remove a key from the named store
+	async fn delete(&self, store: &str, key: &[u8]) -> Result<(), Error>;
+	/// Check whether a key exists in the named store
+	async fn key_exists(&self, store: &str, key: &[u8]) -> Result<bool, Error>;
+	/// Scan a range of key-value pairs from the named store in a direction
+	async fn scan(
+		&self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+		direction: Direction,
+	) -> Result<Vec<(Key, Val)>, Error>;
+	/// Commit the transaction, durably storing all changes
+	async fn commit(&mut self) -> Result<(), Error>;
+	/// Abort the transaction, discarding all changes
+	async fn abort(&mut self) -> Result<(), Error>;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::RexieBackend;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use mem::MemoryBackend;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+	use super::Backend;
+	use super::Direction;
+	use crate::err::Error;
+	use crate::kv::Convert;
+	use crate::kv::Key;
+	use crate::kv::Val;
+	use rexie::Direction as RexieDirection;
+	use rexie::KeyRange;
+	use rexie::Store;
+	use rexie::Transaction as RexieTransaction;
+	use std::ops::Range;
+
+	/// A [`Backend`] backed by the browser's IndexedDB engine via rexie
+	pub struct RexieBackend {
+		/// The underlying database transaction, spanning one or more stores
+		pub(crate) transaction: Option<RexieTransaction>,
+	}
+
+	impl RexieBackend {
+		/// Create a new rexie backend from a transaction
+		pub(crate) fn new(tx: RexieTransaction) -> RexieBackend {
+			RexieBackend {
+				transaction: Some(tx),
+			}
+		}
+
+		/// Obtain a handle to one of the transaction's object stores
+		fn store(&self, store: &str) -> Result<Store, Error> {
+			self.transaction
+				.as_ref()
+				.unwrap()
+				.store(store)
+				.map_err(|_| Error::TxError)
+		}
+	}
+
+	impl Backend for RexieBackend {
+		async fn get(&self, store: &str, key: &[u8]) -> Result<Option<Val>, Error> {
+			let res = self.store(store)?.get(key.to_vec().convert()).await?;
+			match res {
+				Some(v) => Ok(Some(v.convert())),
+				None => Ok(None),
+			}
+		}
+
+		async fn put(&self, store: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+			self.store(store)?
+				.put(&val.to_vec().convert(), Some(&key.to_vec().convert()))
+				.await?;
+			Ok(())
+		}
+
+		async fn delete(&self, store: &str, key: &[u8]) -> Result<(), Error> {
+			self.store(store)?.delete(key.to_vec().convert()).await?;
+			Ok(())
+		}
+
+		async fn key_exists(&self, store: &str, key: &[u8]) -> Result<bool, Error> {
+			let res = self.store(store)?.key_exists(key.to_vec().convert()).await?;
+			Ok(res)
+		}
+
+		async fn scan(
+			&self,
+			store: &str,
+			rng: Range<Key>,
+			limit: u32,
+			direction: Direction,
+		) -> Result<Vec<(Key, Val)>, Error> {
+			// Build the key range and direction for the scan
+			let (rng, dir) = match direction {
+				Direction::Forward => {
+					let rng = KeyRange::bound(
+						&rng.start.convert(),
+						&rng.end.convert(),
+						None,
+						Some(true),
+					);
+					(rng, RexieDirection::Next)
+				}
+				Direction::Reverse => {
+					// For reverse order, we swap the start and end bounds
+					let rng = KeyRange::bound(
+						&rng.end.convert(),
+						&rng.start.convert(),
+						None,
+						Some(true),
+					);
+					(rng, RexieDirection::Prev)
+				}
+			};
+			let rng = rng.map_err(|e| Error::IndexedDbError(e.to_string()))?;
+			// Scan the key-value pairs
+			let res =
+				self.store(store)?.scan(Some(rng), Some(limit), None, Some(dir)).await?;
+			let res = res.into_iter().map(|(k, v)| (k.convert(), v.convert())).collect();
+			Ok(res)
+		}
+
+		async fn commit(&mut self) -> Result<(), Error> {
+			self.transaction.take().unwrap().done().await?;
+			Ok(())
+		}
+
+		async fn abort(&mut self) -> Result<(), Error> {
+			self.transaction.take().unwrap().abort().await?;
+			Ok(())
+		}
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mem {
+	use super::Backend;
+	use super::Direction;
+	use crate::err::Error;
+	use crate::kv::Key;
+	use crate::kv::Val;
+	use std::cell::RefCell;
+	use std::collections::BTreeMap;
+	use std::ops::Range;
+	use std::rc::Rc;
+
+	/// A set of named object stores, each an ordered key-value map
+	type Stores = BTreeMap<String, BTreeMap<Key, Val>>;
+
+	/// The shared in-memory datastore, mirroring rkv's "safe mode"
+	pub(crate) type Store = Rc<RefCell<Stores>>;
+
+	/// The uncommitted changes a transaction has made to each store
+	///
+	/// Changes are recorded as an overlay keyed by store name, where a
+	/// `Some` value is a pending write and a `None` is a pending delete
+	/// (a tombstone). Recording only the changes — rather than cloning the
+	/// whole dataset up front — lets `commit` merge them back key-by-key,
+	/// so writes by other transactions that landed in the meantime are not
+	/// clobbered.
+	type Overlay = BTreeMap<String, BTreeMap<Key, Option<Val>>>;
+
+	/// A [`Backend`] backed by in-memory [`BTreeMap`]s for native testing
+	///
+	/// Each transaction reads through an overlay of its own uncommitted
+	/// changes onto the shared stores, applying those changes atomically on
+	/// `commit` and discarding them on `abort`. The ordered maps give
+	/// correct range semantics for scans.
+	pub struct MemoryBackend {
+		/// The shared stores that commits are merged back into
+		shared: Store,
+		/// The uncommitted changes made by this transaction
+		overlay: RefCell<Overlay>,
+	}
+
+	impl MemoryBackend {
+		/// Create a new backend operating on the given shared datastore
+		pub(crate) fn new(shared: Store) -> MemoryBackend {
+			MemoryBackend {
+				shared,
+				overlay: RefCell::new(BTreeMap::new()),
+			}
+		}
+
+		/// Clone a handle to the shared datastore, so a further transaction
+		/// can be opened against the same underlying state.
+		#[cfg(test)]
+		pub(crate) fn shared(&self) -> Store {
+			Rc::clone(&self.shared)
+		}
+
+		/// Build the merged view of a store: the shared state with this
+		/// transaction's overlay of writes and tombstones applied over it.
+		fn merged(&self, store: &str) -> BTreeMap<Key, Val> {
+			let mut merged = self.shared.borrow().get(store).cloned().unwrap_or_default();
+			if let Some(changes) = self.overlay.borrow().get(store) {
+				for (key, change) in changes {
+					match change {
+						Some(val) => {
+							merged.insert(key.clone(), val.clone());
+						}
+						None => {
+							merged.remove(key);
+						}
+					}
+				}
+			}
+			merged
+		}
+	}
+
+	impl Default for MemoryBackend {
+		/// Create a backend over a fresh, empty datastore
+		fn default() -> MemoryBackend {
+			MemoryBackend::new(Rc::new(RefCell::new(BTreeMap::new())))
+		}
+	}
+
+	impl Backend for MemoryBackend {
+		async fn get(&self, store: &str, key: &[u8]) -> Result<Option<Val>, Error> {
+			// A recorded change shadows the shared state for this key
+			if let Some(change) = self.overlay.borrow().get(store).and_then(|s| s.get(key)) {
+				return Ok(change.clone());
+			}
+			Ok(self.shared.borrow().get(store).and_then(|s| s.get(key).cloned()))
+		}
+
+		async fn put(&self, store: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+			self.overlay
+				.borrow_mut()
+				.entry(store.to_owned())
+				.or_default()
+				.insert(key.to_vec(), Some(val.to_vec()));
+			Ok(())
+		}
+
+		async fn delete(&self, store: &str, key: &[u8]) -> Result<(), Error> {
+			self.overlay
+				.borrow_mut()
+				.entry(store.to_owned())
+				.or_default()
+				.insert(key.to_vec(), None);
+			Ok(())
+		}
+
+		async fn key_exists(&self, store: &str, key: &[u8]) -> Result<bool, Error> {
+			self.get(store, key).await.map(|v| v.is_some())
+		}
+
+		async fn scan(
+			&self,
+			store: &str,
+			rng: Range<Key>,
+			limit: u32,
+			direction: Direction,
+		) -> Result<Vec<(Key, Val)>, Error> {
+			let limit = limit as usize;
+			let merged = self.merged(store);
+			let iter = merged.range(rng);
+			let res = match direction {
+				Direction::Forward => {
+					iter.take(limit).map(|(k, v)| (k.clone(), v.clone())).collect()
+				}
+				Direction::Reverse => {
+					iter.rev().take(limit).map(|(k, v)| (k.clone(), v.clone())).collect()
+				}
+			};
+			Ok(res)
+		}
+
+		async fn commit(&mut self) -> Result<(), Error> {
+			// Merge each recorded change back into the shared datastore,
+			// leaving any keys this transaction never touched untouched
+			let mut shared = self.shared.borrow_mut();
+			for (store, changes) in self.overlay.borrow().iter() {
+				let target = shared.entry(store.clone()).or_default();
+				for (key, change) in changes {
+					match change {
+						Some(val) => {
+							target.insert(key.clone(), val.clone());
+						}
+						None => {
+							target.remove(key);
+						}
+					}
+				}
+			}
+			Ok(())
+		}
+
+		async fn abort(&mut self) -> Result<(), Error> {
+			// Discard the recorded changes without applying them
+			self.overlay.borrow_mut().clear();
+			Ok(())
+		}
+	}
+}
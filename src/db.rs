@@ -13,47 +13,115 @@
 // limitations under the License.
 
 //! This module stores the core IndexedDB database type.
+#![cfg(target_arch = "wasm32")]
 
+use crate::backend::RexieBackend;
 use crate::err::Error;
+use crate::tx::Runner;
 use crate::tx::Transaction;
 use rexie::ObjectStore;
 use rexie::Rexie;
 use rexie::TransactionMode;
 
+/// The default total number of attempts made at a writeable transaction when
+/// it is aborted by the IndexedDB engine under contention.
+const DEFAULT_TRANSACTION_ATTEMPTS: usize = 5;
+
 /// A transactional browser-based database
 pub struct Database {
 	/// The underlying IndexedDB datastore
 	pub(crate) datastore: Rexie,
+	/// The total number of attempts made at a writeable transaction if aborted
+	pub(crate) attempts: usize,
 }
 
 impl Database {
 	/// Create a new transactional IndexedDB database
-	pub async fn new(path: &str) -> Result<Self, Error> {
-		// Create the new object store
-		let store = ObjectStore::new("kv");
+	///
+	/// Each name in `stores` is registered as an object store, allowing a
+	/// single database to hold several isolated namespaces. Bumping
+	/// `version` re-runs the object-store creation in the builder's upgrade
+	/// callback, providing a migration path for new stores.
+	pub async fn new(path: &str, stores: &[&str], version: u32) -> Result<Self, Error> {
+		// Register each requested object store with the database
+		let mut builder = Rexie::builder(path).version(version);
+		for store in stores {
+			builder = builder.add_object_store(ObjectStore::new(store));
+		}
 		// Build and initialise the database
-		match Rexie::builder(path).version(1).add_object_store(store).build().await {
+		match builder.build().await {
 			Ok(db) => Ok(Database {
 				datastore: db,
+				attempts: DEFAULT_TRANSACTION_ATTEMPTS,
 			}),
 			Err(_) => Err(Error::DbError),
 		}
 	}
 
-	/// Start a new read-only or writeable transaction
-	pub async fn begin(&self, write: bool) -> Result<Transaction, Error> {
+	/// Start a new read-only or writeable transaction over the given stores
+	///
+	/// The transaction spans every named store atomically; the first store
+	/// becomes the transaction's default for the unqualified `get`/`set`
+	/// methods, while [`Transaction::store`] selects the others.
+	pub async fn begin(
+		&self,
+		stores: &[&str],
+		write: bool,
+	) -> Result<Transaction<RexieBackend>, Error> {
+		// Every transaction must operate over at least one store
+		let default = match stores.first() {
+			Some(store) => *store,
+			None => return Err(Error::TxError),
+		};
 		// Set the transaction mode
 		let mode = match write {
 			true => TransactionMode::ReadWrite,
 			false => TransactionMode::ReadOnly,
 		};
 		// Create the new transaction
-		match self.datastore.transaction(&["kv"], mode) {
-			Ok(tx) => match tx.store("kv") {
-				Ok(st) => Ok(Transaction::new(tx, st, write)),
-				Err(_) => Err(Error::TxError),
-			},
+		match self.datastore.transaction(stores, mode) {
+			Ok(tx) => Ok(Transaction::new(RexieBackend::new(tx), write, default)),
 			Err(_) => Err(Error::TxError),
 		}
 	}
+
+	/// Run a closure within a transaction, finalising it automatically
+	///
+	/// A fresh transaction is started and passed to the async closure. If
+	/// the closure resolves to `Ok`, the transaction is committed (a read-only
+	/// one is closed instead) and the value is returned; if it resolves to
+	/// `Err`, the transaction is
+	/// cancelled and the error is propagated. This ensures callers can never
+	/// leak an un-finalized [`Transaction`]. Because IndexedDB may abort a
+	/// `ReadWrite` transaction under contention, the closure is re-run
+	/// against a newly begun transaction if the commit is aborted, up to a
+	/// bounded number of attempts. The closure is async so it can `await`
+	/// the transaction's store operations directly.
+	pub async fn transaction<F, T>(
+		&self,
+		stores: &[&str],
+		write: bool,
+		f: F,
+	) -> Result<T, Error>
+	where
+		F: AsyncFnMut(&mut Transaction<RexieBackend>) -> Result<T, Error>,
+	{
+		Runner::run(self, stores, write, f).await
+	}
+}
+
+impl Runner for Database {
+	type Backend = RexieBackend;
+
+	fn attempts(&self) -> usize {
+		self.attempts
+	}
+
+	async fn begin_tx(
+		&self,
+		stores: &[&str],
+		write: bool,
+	) -> Result<Transaction<RexieBackend>, Error> {
+		self.begin(stores, write).await
+	}
 }
@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(target_arch = "wasm32")]
 use js_sys::ArrayBuffer;
+#[cfg(target_arch = "wasm32")]
 use js_sys::Uint8Array;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::{JsCast, JsValue};
 
 pub type Key = Vec<u8>;
@@ -25,10 +28,45 @@ pub struct Kv {
 	pub val: Val,
 }
 
+/// An atomic mutation operator applied to an existing stored value
+///
+/// These mirror the FoundationDB atomic operators. Each combines the
+/// existing value (a missing key is treated as all-zero bytes) with a
+/// supplied parameter, after zero-padding or truncating both operands to a
+/// common length.
+pub enum AtomicOp {
+	/// Add both operands as little-endian unsigned integers, wrapping
+	Add,
+	/// Keep the smaller of the two little-endian unsigned integers
+	Min,
+	/// Keep the larger of the two little-endian unsigned integers
+	Max,
+	/// Combine the operands with a bitwise AND
+	BitAnd,
+	/// Combine the operands with a bitwise OR
+	BitOr,
+	/// Combine the operands with a bitwise XOR
+	BitXor,
+	/// Keep the lexicographically smaller byte string
+	ByteMin,
+	/// Keep the lexicographically larger byte string
+	ByteMax,
+	/// Overwrite the value with the parameter verbatim
+	///
+	/// No versionstamp is generated or stamped into the value; this is
+	/// currently a plain alias for [`Transaction::set`] retained for
+	/// operator parity with FoundationDB.
+	///
+	/// [`Transaction::set`]: crate::tx::Transaction::set
+	SetVersionstamp,
+}
+
+#[cfg(target_arch = "wasm32")]
 pub trait Convert<T> {
 	fn convert(self) -> T;
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Convert<Vec<u8>> for JsValue {
 	fn convert(self) -> Vec<u8> {
 		if self.has_type::<ArrayBuffer>() {
@@ -43,6 +81,7 @@ impl Convert<Vec<u8>> for JsValue {
 	}
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Convert<JsValue> for Vec<u8> {
 	fn convert(self) -> JsValue {
 		JsValue::from(Uint8Array::from(&self[..]))
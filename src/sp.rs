@@ -18,19 +18,24 @@ use crate::kv::Key;
 use crate::kv::Val;
 
 /// A savepoint state capturing operations that can be undone
-#[derive(Debug, Clone)]
 pub(crate) struct Savepoint {
 	/// Operations that can be undone to rollback to this savepoint
 	pub(crate) operations: Vec<Operation>,
+	/// Post-commit callbacks queued before this savepoint was set
+	pub(crate) on_commit: Vec<Box<dyn FnOnce()>>,
 }
 
 /// An operation that can be undone during savepoint rollback
+///
+/// Each operation records the object store it applies to, so that a
+/// transaction spanning several stores rolls each change back into the
+/// correct namespace.
 #[derive(Debug, Clone)]
 pub(crate) enum Operation {
 	/// Delete a key that was inserted
-	DeleteKey(Key),
+	DeleteKey(String, Key),
 	/// Restore a key to its previous value
-	RestoreValue(Key, Val),
+	RestoreValue(String, Key, Val),
 	/// Restore a key that was deleted (insert it back)
-	RestoreDeleted(Key, Val),
+	RestoreDeleted(String, Key, Val),
 }
@@ -14,47 +14,79 @@
 
 //! This module stores the database transaction logic.
 
+use crate::backend::Backend;
+use crate::backend::Direction;
 use crate::err::Error;
-use crate::kv::Convert;
+use crate::kv::AtomicOp;
 use crate::kv::Key;
 use crate::kv::Val;
 use crate::sp::Operation;
 use crate::sp::Savepoint;
-use rexie::Direction;
-use rexie::KeyRange;
-use rexie::Store;
-use rexie::Transaction as RexieTransaction;
 use std::ops::Range;
 
 /// A serializable snapshot isolated database transaction
-pub struct Transaction {
+pub struct Transaction<B: Backend> {
 	/// Is the transaction complete?
 	pub(crate) done: bool,
 	/// Is the transaction read+write?
 	pub(crate) write: bool,
-	/// The underlying database store
-	pub(crate) datastore: Option<Store>,
-	/// The underlying database transaction
-	pub(crate) transaction: Option<RexieTransaction>,
+	/// The name of the default object store
+	pub(crate) store: String,
+	/// The underlying store backend
+	pub(crate) backend: B,
 	/// Stack of savepoints for nested rollback support
 	pub(crate) savepoints: Vec<Savepoint>,
 	/// Current undo operations since the last savepoint
 	pub(crate) operations: Vec<Operation>,
+	/// Callbacks to invoke once the transaction durably commits
+	pub(crate) on_commit: Vec<Box<dyn FnOnce()>>,
 }
 
-impl Transaction {
-	/// Create a new transaction
-	pub(crate) fn new(tx: RexieTransaction, st: Store, write: bool) -> Transaction {
+/// A handle to a single named object store within a [`Transaction`]
+///
+/// Store handles let a single transaction read and write several isolated
+/// namespaces — for example separating indexes, metadata, and data — while
+/// still committing them together. Obtain one with [`Transaction::store`].
+pub struct Store {
+	/// The name of the object store this handle refers to
+	name: String,
+}
+
+impl<B: Backend> Transaction<B> {
+	/// Create a new transaction over the given default object store
+	#[cfg(any(target_arch = "wasm32", test))]
+	pub(crate) fn new(backend: B, write: bool, store: impl Into<String>) -> Transaction<B> {
 		Transaction {
 			done: false,
 			write,
-			datastore: Some(st),
-			transaction: Some(tx),
+			store: store.into(),
+			backend,
 			savepoints: Vec::new(),
 			operations: Vec::new(),
+			on_commit: Vec::new(),
+		}
+	}
+
+	/// Obtain a handle to one of the transaction's object stores
+	pub fn store(&self, name: &str) -> Store {
+		Store {
+			name: name.to_owned(),
 		}
 	}
 
+	/// Register a callback to run once the transaction durably commits
+	///
+	/// The callback fires, in registration order, only after the underlying
+	/// IndexedDB transaction has successfully committed. This is the correct
+	/// place to invalidate caches, fire change notifications, or wake
+	/// watchers, as it cannot race ahead of the durable write. Callbacks
+	/// queued since the last savepoint are discarded on
+	/// `rollback_to_savepoint`, and all callbacks are dropped without
+	/// running if the transaction is cancelled.
+	pub fn on_commit(&mut self, f: impl FnOnce() + 'static) {
+		self.on_commit.push(Box::new(f));
+	}
+
 	/// Check if the transaction is closed
 	pub fn closed(&self) -> bool {
 		self.done
@@ -69,7 +101,7 @@ impl Transaction {
 		// Mark this transaction as done
 		self.done = true;
 		// Abort the indexdb transaction
-		self.transaction.take().unwrap().abort().await?;
+		self.backend.abort().await?;
 		// Continue
 		Ok(())
 	}
@@ -87,40 +119,280 @@ impl Transaction {
 		// Mark this transaction as done
 		self.done = true;
 		// Commit the indexdb transaction
-		self.transaction.take().unwrap().done().await?;
+		self.backend.commit().await?;
+		// Collect every registered callback in registration order
+		let mut callbacks = Vec::new();
+		for savepoint in self.savepoints.drain(..) {
+			callbacks.extend(savepoint.on_commit);
+		}
+		callbacks.append(&mut self.on_commit);
+		// Fire the callbacks now that the changes have durably landed
+		for callback in callbacks {
+			callback();
+		}
 		// Continue
 		Ok(())
 	}
 
-	/// Check if a key exists in the database
+	/// Check if a key exists in the default store
 	pub async fn exists(&mut self, key: Key) -> Result<bool, Error> {
+		let store = self.store.clone();
+		self.exists_in(&store, key).await
+	}
+
+	/// Fetch a key from the default store
+	pub async fn get(&mut self, key: Key) -> Result<Option<Val>, Error> {
+		let store = self.store.clone();
+		self.get_in(&store, key).await
+	}
+
+	/// Insert or update a key in the default store
+	pub async fn set(&mut self, key: Key, val: Val) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.set_in(&store, key, val).await
+	}
+
+	/// Insert a key if it doesn't exist in the default store
+	pub async fn put(&mut self, key: Key, val: Val) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.put_in(&store, key, val).await
+	}
+
+	/// Insert a key if it matches a value in the default store
+	pub async fn putc(&mut self, key: Key, val: Val, chk: Option<Val>) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.putc_in(&store, key, val, chk).await
+	}
+
+	/// Delete a key from the default store
+	pub async fn del(&mut self, key: Key) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.del_in(&store, key).await
+	}
+
+	/// Delete a key if it matches a value in the default store
+	pub async fn delc(&mut self, key: Key, chk: Option<Val>) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.delc_in(&store, key, chk).await
+	}
+
+	/// Retrieve a range of keys from the default store
+	pub async fn keys(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<Key>, Error> {
+		let store = self.store.clone();
+		self.keys_in(&store, rng, limit).await
+	}
+
+	/// Retrieve a range of keys from the default store in reverse order
+	pub async fn keysr(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<Key>, Error> {
+		let store = self.store.clone();
+		self.keysr_in(&store, rng, limit).await
+	}
+
+	/// Retrieve a range of key-value pairs from the default store
+	pub async fn scan(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<(Key, Val)>, Error> {
+		let store = self.store.clone();
+		self.scan_in(&store, rng, limit).await
+	}
+
+	/// Retrieve a range of key-value pairs from the default store in reverse
+	pub async fn scanr(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<(Key, Val)>, Error> {
+		let store = self.store.clone();
+		self.scanr_in(&store, rng, limit).await
+	}
+
+	/// Delete a range of keys from the default store
+	pub async fn delr(&mut self, rng: Range<Key>, limit: u32) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.delr_in(&store, rng, limit).await
+	}
+
+	/// Fetch several keys from the default store in one batch
+	pub async fn getm(&mut self, keys: Vec<Key>) -> Result<Vec<Option<Val>>, Error> {
+		let store = self.store.clone();
+		self.getm_in(&store, keys).await
+	}
+
+	/// Insert or update several key-value pairs in the default store in one batch
+	pub async fn setm(&mut self, pairs: Vec<(Key, Val)>) -> Result<(), Error> {
+		let store = self.store.clone();
+		self.setm_in(&store, pairs).await
+	}
+
+	/// Atomically mutate a key in the default store using an operator
+	///
+	/// The existing value is read (a missing key is treated as all-zero
+	/// bytes), both operands are zero-padded or truncated to a common
+	/// length, and the result is combined according to `op` before being
+	/// written back. The final write is funnelled through `set`, so the
+	/// mutation records the correct undo operation and participates in
+	/// savepoint rollback.
+	pub async fn atomic(&mut self, key: Key, param: Val, op: AtomicOp) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.done {
+			return Err(Error::TxClosed);
+		}
+		// Check to see if transaction is writable
+		if !self.write {
+			return Err(Error::TxNotWritable);
+		}
+		// A versionstamp simply overwrites the value with the parameter
+		if let AtomicOp::SetVersionstamp = op {
+			return self.set(key, param).await;
+		}
+		// Read the existing value, treating a missing key as empty
+		let existing = self.get(key.clone()).await?.unwrap_or_default();
+		// Pad both operands to a common length
+		let len = existing.len().max(param.len());
+		let mut lhs = existing;
+		let mut rhs = param;
+		lhs.resize(len, 0);
+		rhs.resize(len, 0);
+		// Combine the operands according to the operator
+		let res = match op {
+			AtomicOp::Add => {
+				// Add as little-endian unsigned integers, wrapping on overflow
+				let mut out = vec![0u8; len];
+				let mut carry = 0u16;
+				for i in 0..len {
+					let sum = lhs[i] as u16 + rhs[i] as u16 + carry;
+					out[i] = sum as u8;
+					carry = sum >> 8;
+				}
+				out
+			}
+			AtomicOp::Min => {
+				// Keep the smaller little-endian unsigned integer
+				if le_cmp(&lhs, &rhs).is_le() {
+					lhs
+				} else {
+					rhs
+				}
+			}
+			AtomicOp::Max => {
+				// Keep the larger little-endian unsigned integer
+				if le_cmp(&lhs, &rhs).is_ge() {
+					lhs
+				} else {
+					rhs
+				}
+			}
+			AtomicOp::BitAnd => lhs.iter().zip(&rhs).map(|(a, b)| a & b).collect(),
+			AtomicOp::BitOr => lhs.iter().zip(&rhs).map(|(a, b)| a | b).collect(),
+			AtomicOp::BitXor => lhs.iter().zip(&rhs).map(|(a, b)| a ^ b).collect(),
+			AtomicOp::ByteMin => {
+				// Keep the lexicographically smaller byte string
+				if lhs <= rhs {
+					lhs
+				} else {
+					rhs
+				}
+			}
+			AtomicOp::ByteMax => {
+				// Keep the lexicographically larger byte string
+				if lhs >= rhs {
+					lhs
+				} else {
+					rhs
+				}
+			}
+			// Handled above before reading the existing value
+			AtomicOp::SetVersionstamp => unreachable!(),
+		};
+		// Write the result back through the undo-recording path
+		self.set(key, res).await
+	}
+
+	/// Set a savepoint in the transaction for partial rollback
+	/// This method is stackable and can be called multiple times with
+	/// corresponding calls to `rollback_to_savepoint`
+	pub async fn set_savepoint(&mut self) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.done {
+			return Err(Error::TxClosed);
+		}
+		// Check to see if transaction is writable
+		if !self.write {
+			return Err(Error::TxNotWritable);
+		}
+		// Create a new savepoint with current operations and callbacks
+		self.savepoints.push(Savepoint {
+			operations: std::mem::take(&mut self.operations),
+			on_commit: std::mem::take(&mut self.on_commit),
+		});
+		// Continue
+		Ok(())
+	}
+
+	/// Rollback the transaction to the most recently set savepoint
+	/// After calling this method, subsequent modifications within this
+	/// transaction can be rolled back by calling `rollback_to_savepoint`
+	/// again if there are more savepoints in the stack
+	pub async fn rollback_to_savepoint(&mut self) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.done {
+			return Err(Error::TxClosed);
+		}
+		// Check to see if transaction is writable
+		if !self.write {
+			return Err(Error::TxNotWritable);
+		}
+		// Check if there are any savepoints
+		if self.savepoints.is_empty() {
+			return Err(Error::NoSavepoint);
+		}
+		// Get the most recent savepoint
+		let savepoint = self.savepoints.pop().unwrap();
+		// Execute undo operations in reverse order
+		for op in self.operations.iter().rev() {
+			match op {
+				// Delete the key that was inserted
+				Operation::DeleteKey(store, key) => {
+					self.backend.delete(store, key).await?;
+				}
+				// Restore the previous value
+				Operation::RestoreValue(store, key, val) => {
+					self.backend.put(store, key, val).await?;
+				}
+				// Restore the deleted key
+				Operation::RestoreDeleted(store, key, val) => {
+					self.backend.put(store, key, val).await?;
+				}
+			}
+		}
+		// Discard callbacks queued since the savepoint, restoring the rest
+		self.on_commit = savepoint.on_commit;
+		// Restore the savepoint's operations as the current ones
+		self.operations = savepoint.operations;
+		// Continue
+		Ok(())
+	}
+
+	/// Check if a key exists in the named store
+	pub(crate) async fn exists_in(&mut self, store: &str, key: Key) -> Result<bool, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
 		// Check the key
-		let res = self.datastore.as_ref().unwrap().key_exists(key.convert()).await?;
+		let res = self.backend.key_exists(store, &key).await?;
 		// Return result
 		Ok(res)
 	}
 
-	/// Fetch a key from the database
-	pub async fn get(&mut self, key: Key) -> Result<Option<Val>, Error> {
+	/// Fetch a key from the named store
+	pub(crate) async fn get_in(&mut self, store: &str, key: Key) -> Result<Option<Val>, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
 		// Get the key
-		let res = self.datastore.as_ref().unwrap().get(key.convert()).await?;
+		let res = self.backend.get(store, &key).await?;
 		// Return result
-		match res {
-			Some(v) => Ok(Some(v.convert())),
-			None => Ok(None),
-		}
+		Ok(res)
 	}
 
-	/// Insert or update a key in the database
-	pub async fn set(&mut self, key: Key, val: Val) -> Result<(), Error> {
+	/// Insert or update a key in the named store
+	pub(crate) async fn set_in(&mut self, store: &str, key: Key, val: Val) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -132,25 +404,29 @@ impl Transaction {
 		// Record operation if we have savepoints
 		if !self.savepoints.is_empty() || !self.operations.is_empty() {
 			// Check if key already exists to determine undo operation
-			match self.get(key.clone()).await? {
+			match self.get_in(store, key.clone()).await? {
 				Some(existing_val) => {
 					// Key exists, record operation to restore old value
-					self.operations.push(Operation::RestoreValue(key.clone(), existing_val));
+					self.operations.push(Operation::RestoreValue(
+						store.to_owned(),
+						key.clone(),
+						existing_val,
+					));
 				}
 				None => {
 					// Key doesn't exist, record operation to delete it
-					self.operations.push(Operation::DeleteKey(key.clone()));
+					self.operations.push(Operation::DeleteKey(store.to_owned(), key.clone()));
 				}
 			}
 		}
 		// Set the key
-		self.datastore.as_ref().unwrap().put(&val.convert(), Some(&key.convert())).await?;
+		self.backend.put(store, &key, &val).await?;
 		// Return result
 		Ok(())
 	}
 
-	/// Insert a key if it doesn't exist in the database
-	pub async fn put(&mut self, key: Key, val: Val) -> Result<(), Error> {
+	/// Insert a key if it doesn't exist in the named store
+	pub(crate) async fn put_in(&mut self, store: &str, key: Key, val: Val) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -160,16 +436,22 @@ impl Transaction {
 			return Err(Error::TxNotWritable);
 		}
 		// Set the key
-		match self.get(key.clone()).await? {
-			None => self.set(key, val).await?,
+		match self.get_in(store, key.clone()).await? {
+			None => self.set_in(store, key, val).await?,
 			_ => return Err(Error::KeyAlreadyExists),
 		};
 		// Return result
 		Ok(())
 	}
 
-	/// Insert a key if it matches a value
-	pub async fn putc(&mut self, key: Key, val: Val, chk: Option<Val>) -> Result<(), Error> {
+	/// Insert a key if it matches a value in the named store
+	pub(crate) async fn putc_in(
+		&mut self,
+		store: &str,
+		key: Key,
+		val: Val,
+		chk: Option<Val>,
+	) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -179,17 +461,17 @@ impl Transaction {
 			return Err(Error::TxNotWritable);
 		}
 		// Set the key
-		match (self.get(key.clone()).await?, chk) {
-			(Some(v), Some(w)) if v == w => self.set(key, val).await?,
-			(None, None) => self.set(key, val).await?,
+		match (self.get_in(store, key.clone()).await?, chk) {
+			(Some(v), Some(w)) if v == w => self.set_in(store, key, val).await?,
+			(None, None) => self.set_in(store, key, val).await?,
 			_ => return Err(Error::ValNotExpectedValue),
 		};
 		// Return result
 		Ok(())
 	}
 
-	/// Delete a key from the database
-	pub async fn del(&mut self, key: Key) -> Result<(), Error> {
+	/// Delete a key from the named store
+	pub(crate) async fn del_in(&mut self, store: &str, key: Key) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -201,19 +483,28 @@ impl Transaction {
 		// Record operation if we have savepoints
 		if !self.savepoints.is_empty() || !self.operations.is_empty() {
 			// Check if key exists to record restoration operation
-			if let Some(existing_val) = self.get(key.clone()).await? {
+			if let Some(existing_val) = self.get_in(store, key.clone()).await? {
 				// Key exists, record operation to restore it
-				self.operations.push(Operation::RestoreDeleted(key.clone(), existing_val));
+				self.operations.push(Operation::RestoreDeleted(
+					store.to_owned(),
+					key.clone(),
+					existing_val,
+				));
 			}
 		}
 		// Remove the key
-		self.datastore.as_ref().unwrap().delete(key.convert()).await?;
+		self.backend.delete(store, &key).await?;
 		// Return result
 		Ok(())
 	}
 
-	/// Delete a key if it matches a value
-	pub async fn delc(&mut self, key: Key, chk: Option<Val>) -> Result<(), Error> {
+	/// Delete a key if it matches a value in the named store
+	pub(crate) async fn delc_in(
+		&mut self,
+		store: &str,
+		key: Key,
+		chk: Option<Val>,
+	) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -223,93 +514,92 @@ impl Transaction {
 			return Err(Error::TxNotWritable);
 		}
 		// Remove the key
-		match (self.get(key.clone()).await?, chk) {
-			(Some(v), Some(w)) if v == w => self.del(key).await?,
-			(None, None) => self.del(key).await?,
+		match (self.get_in(store, key.clone()).await?, chk) {
+			(Some(v), Some(w)) if v == w => self.del_in(store, key).await?,
+			(None, None) => self.del_in(store, key).await?,
 			_ => return Err(Error::ValNotExpectedValue),
 		};
 		// Return result
 		Ok(())
 	}
 
-	/// Retrieve a range of keys from the databases
-	pub async fn keys(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<Key>, Error> {
+	/// Retrieve a range of keys from the named store
+	pub(crate) async fn keys_in(
+		&mut self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<Key>, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
-		// Get the iteration direction
-		let dir = Some(Direction::Next);
-		// Convert the range to JavaScript
-		let rng = KeyRange::bound(&rng.start.convert(), &rng.end.convert(), None, Some(true));
-		let rng = rng.map_err(|e| Error::IndexedDbError(e.to_string()))?;
 		// Scan the keys
-		let res = self.datastore.as_ref().unwrap().scan(Some(rng), Some(limit), None, dir).await?;
-		let res = res.into_iter().map(|(k, _)| k.convert()).collect();
+		let res = self.backend.scan(store, rng, limit, Direction::Forward).await?;
+		let res = res.into_iter().map(|(k, _)| k).collect();
 		// Return result
 		Ok(res)
 	}
 
-	/// Retrieve a range of keys from the databases in reverse order
-	pub async fn keysr(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<Key>, Error> {
+	/// Retrieve a range of keys from the named store in reverse order
+	pub(crate) async fn keysr_in(
+		&mut self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<Key>, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
-		// Get the iteration direction
-		let dir = Some(Direction::Prev);
-		// Convert the range to JavaScript for reverse scanning
-		// For reverse order, we need to swap the start and end bounds
-		let rng = KeyRange::bound(&rng.end.convert(), &rng.start.convert(), None, Some(true));
-		let rng = rng.map_err(|e| Error::IndexedDbError(e.to_string()))?;
 		// Scan the keys in reverse order
-		let res = self.datastore.as_ref().unwrap().scan(Some(rng), Some(limit), None, dir).await?;
-		let res = res.into_iter().map(|(k, _)| k.convert()).collect();
+		let res = self.backend.scan(store, rng, limit, Direction::Reverse).await?;
+		let res = res.into_iter().map(|(k, _)| k).collect();
 		// Return result
 		Ok(res)
 	}
 
-	/// Retrieve a range of key-value pairs from the databases
-	pub async fn scan(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<(Key, Val)>, Error> {
+	/// Retrieve a range of key-value pairs from the named store
+	pub(crate) async fn scan_in(
+		&mut self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
-		// Get the iteration direction
-		let dir = Some(Direction::Next);
-		// Convert the range to JavaScript
-		let rng = KeyRange::bound(&rng.start.convert(), &rng.end.convert(), None, Some(true));
-		let rng = rng.map_err(|e| Error::IndexedDbError(e.to_string()))?;
-		// Scan the keys
-		let res = self.datastore.as_ref().unwrap().scan(Some(rng), Some(limit), None, dir).await?;
-		let res = res.into_iter().map(|(k, v)| (k.convert(), v.convert())).collect();
+		// Scan the key-value pairs
+		let res = self.backend.scan(store, rng, limit, Direction::Forward).await?;
 		// Return result
 		Ok(res)
 	}
 
-	/// Retrieve a range of key-value pairs from the databases in reverse order
-	pub async fn scanr(&mut self, rng: Range<Key>, limit: u32) -> Result<Vec<(Key, Val)>, Error> {
+	/// Retrieve a range of key-value pairs from the named store in reverse
+	pub(crate) async fn scanr_in(
+		&mut self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
 		}
-		// Get the iteration direction
-		let dir = Some(Direction::Prev);
-		// Convert the range to JavaScript for reverse scanning
-		// For reverse order, we need to swap the start and end bounds
-		let rng = KeyRange::bound(&rng.end.convert(), &rng.start.convert(), None, Some(true));
-		let rng = rng.map_err(|e| Error::IndexedDbError(e.to_string()))?;
-		// Scan the keys in reverse order
-		let res = self.datastore.as_ref().unwrap().scan(Some(rng), Some(limit), None, dir).await?;
-		let res = res.into_iter().map(|(k, v)| (k.convert(), v.convert())).collect();
+		// Scan the key-value pairs in reverse order
+		let res = self.backend.scan(store, rng, limit, Direction::Reverse).await?;
 		// Return result
 		Ok(res)
 	}
 
-	/// Set a savepoint in the transaction for partial rollback
-	/// This method is stackable and can be called multiple times with
-	/// corresponding calls to `rollback_to_savepoint`
-	pub async fn set_savepoint(&mut self) -> Result<(), Error> {
+	/// Delete a range of keys from the named store
+	pub(crate) async fn delr_in(
+		&mut self,
+		store: &str,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -318,19 +608,49 @@ impl Transaction {
 		if !self.write {
 			return Err(Error::TxNotWritable);
 		}
-		// Create a new savepoint with current operations
-		self.savepoints.push(Savepoint {
-			operations: std::mem::take(&mut self.operations),
-		});
-		// Continue
+		// Scan the matching keys, then delete each one
+		let keys = self.keys_in(store, rng, limit).await?;
+		for key in keys {
+			// Deleting records a `RestoreDeleted` undo operation while a
+			// savepoint is set
+			self.del_in(store, key).await?;
+		}
+		// Return result
 		Ok(())
 	}
 
-	/// Rollback the transaction to the most recently set savepoint
-	/// After calling this method, subsequent modifications within this
-	/// transaction can be rolled back by calling `rollback_to_savepoint`
-	/// again if there are more savepoints in the stack
-	pub async fn rollback_to_savepoint(&mut self) -> Result<(), Error> {
+	/// Fetch several keys from the named store in one batch
+	pub(crate) async fn getm_in(
+		&mut self,
+		store: &str,
+		keys: Vec<Key>,
+	) -> Result<Vec<Option<Val>>, Error> {
+		// Check to see if transaction is closed
+		if self.done {
+			return Err(Error::TxClosed);
+		}
+		// Reads record no undo, so issue every backend fetch together and
+		// await them as a group rather than paying a round-trip per key
+		let futures = keys.iter().map(|key| self.backend.get(store, key));
+		let res = futures::future::join_all(futures).await;
+		// Collect the results in order, propagating the first error
+		res.into_iter().collect()
+	}
+
+	/// Insert or update several key-value pairs in the named store in one batch
+	///
+	/// Unlike [`getm_in`], the writes are applied sequentially: each `set`
+	/// reads the existing value to record the correct savepoint undo
+	/// operation through `&mut self`, which cannot be borrowed concurrently.
+	/// The batching still saves callers the per-call boilerplate and keeps a
+	/// single entry point for amortising writes within one transaction.
+	///
+	/// [`getm_in`]: Transaction::getm_in
+	pub(crate) async fn setm_in(
+		&mut self,
+		store: &str,
+		pairs: Vec<(Key, Val)>,
+	) -> Result<(), Error> {
 		// Check to see if transaction is closed
 		if self.done {
 			return Err(Error::TxClosed);
@@ -339,40 +659,471 @@ impl Transaction {
 		if !self.write {
 			return Err(Error::TxNotWritable);
 		}
-		// Check if there are any savepoints
-		if self.savepoints.is_empty() {
-			return Err(Error::NoSavepoint);
+		// Set each key-value pair in turn, recording undo as we go
+		for (key, val) in pairs {
+			self.set_in(store, key, val).await?;
 		}
-		// Get the most recent savepoint
-		let savepoint = self.savepoints.pop().unwrap();
-		// Execute undo operations in reverse order
-		for op in self.operations.iter().rev() {
-			match op {
-				// Delete the key that was inserted
-				Operation::DeleteKey(key) => {
-					self.datastore.as_ref().unwrap().delete(key.clone().convert()).await?;
-				}
-				// Restore the previous value
-				Operation::RestoreValue(key, val) => {
-					self.datastore
-						.as_ref()
-						.unwrap()
-						.put(&val.clone().convert(), Some(&key.clone().convert()))
-						.await?;
+		// Return result
+		Ok(())
+	}
+}
+
+/// A source of transactions that can run a closure with automatic finalising
+///
+/// Abstracting how a transaction is begun lets the commit/cancel/retry loop
+/// be shared between the IndexedDB-backed [`Database`] and the in-memory
+/// backend used for native testing, rather than being stranded on the
+/// `wasm32`-only [`Database`] type.
+///
+/// [`Database`]: crate::db::Database
+#[cfg(any(target_arch = "wasm32", test))]
+#[allow(async_fn_in_trait)]
+pub(crate) trait Runner {
+	/// The store backend the begun transactions operate on
+	type Backend: Backend;
+
+	/// The total number of attempts made at a writeable transaction if aborted
+	fn attempts(&self) -> usize;
+
+	/// Begin a new transaction over the given stores
+	async fn begin_tx(
+		&self,
+		stores: &[&str],
+		write: bool,
+	) -> Result<Transaction<Self::Backend>, Error>;
+
+	/// Run a closure within a transaction, finalising it automatically
+	///
+	/// The closure is passed a mutable transaction and awaited. On `Ok` the
+	/// transaction is committed (a read-only one is closed instead) and the
+	/// value returned; on `Err` it is cancelled and the error propagated, so
+	/// a transaction can never be leaked un-finalized. If the commit is
+	/// aborted under contention the closure is re-run against a freshly
+	/// begun transaction, running at most [`attempts`](Runner::attempts)
+	/// times in all.
+	async fn run<F, T>(&self, stores: &[&str], write: bool, mut f: F) -> Result<T, Error>
+	where
+		F: AsyncFnMut(&mut Transaction<Self::Backend>) -> Result<T, Error>,
+	{
+		// Track the number of attempts made
+		let mut attempt = 0;
+		loop {
+			// Record another attempt
+			attempt += 1;
+			// Begin a new transaction
+			let mut tx = self.begin_tx(stores, write).await?;
+			// Run the closure against the transaction
+			match f(&mut tx).await {
+				// A read-only transaction has nothing to commit, so close it
+				Ok(val) if !write => {
+					tx.cancel().await?;
+					return Ok(val);
 				}
-				// Restore the deleted key
-				Operation::RestoreDeleted(key, val) => {
-					self.datastore
-						.as_ref()
-						.unwrap()
-						.put(&val.clone().convert(), Some(&key.clone().convert()))
-						.await?;
+				// The closure succeeded, so commit the transaction
+				Ok(val) => match tx.commit().await {
+					// The transaction committed successfully
+					Ok(()) => return Ok(val),
+					// The transaction was aborted under contention, so retry
+					Err(Error::IndexedDbError(msg))
+						if msg.to_lowercase().contains("abort")
+							&& attempt < self.attempts() =>
+					{
+						continue
+					}
+					// The transaction failed for another reason
+					Err(err) => return Err(err),
+				},
+				// The closure failed, so cancel the transaction
+				Err(err) => {
+					tx.cancel().await?;
+					return Err(err);
 				}
 			}
 		}
-		// Restore the savepoint's operations as the current ones
-		self.operations = savepoint.operations;
-		// Continue
-		Ok(())
+	}
+}
+
+impl Store {
+	/// Check if a key exists in this store
+	pub async fn exists<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+	) -> Result<bool, Error> {
+		tx.exists_in(&self.name, key).await
+	}
+
+	/// Fetch a key from this store
+	pub async fn get<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+	) -> Result<Option<Val>, Error> {
+		tx.get_in(&self.name, key).await
+	}
+
+	/// Insert or update a key in this store
+	pub async fn set<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+		val: Val,
+	) -> Result<(), Error> {
+		tx.set_in(&self.name, key, val).await
+	}
+
+	/// Insert a key if it doesn't exist in this store
+	pub async fn put<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+		val: Val,
+	) -> Result<(), Error> {
+		tx.put_in(&self.name, key, val).await
+	}
+
+	/// Insert a key if it matches a value in this store
+	pub async fn putc<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+		val: Val,
+		chk: Option<Val>,
+	) -> Result<(), Error> {
+		tx.putc_in(&self.name, key, val, chk).await
+	}
+
+	/// Delete a key from this store
+	pub async fn del<B: Backend>(&self, tx: &mut Transaction<B>, key: Key) -> Result<(), Error> {
+		tx.del_in(&self.name, key).await
+	}
+
+	/// Delete a key if it matches a value in this store
+	pub async fn delc<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		key: Key,
+		chk: Option<Val>,
+	) -> Result<(), Error> {
+		tx.delc_in(&self.name, key, chk).await
+	}
+
+	/// Retrieve a range of keys from this store
+	pub async fn keys<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<Key>, Error> {
+		tx.keys_in(&self.name, rng, limit).await
+	}
+
+	/// Retrieve a range of keys from this store in reverse order
+	pub async fn keysr<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<Key>, Error> {
+		tx.keysr_in(&self.name, rng, limit).await
+	}
+
+	/// Retrieve a range of key-value pairs from this store
+	pub async fn scan<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error> {
+		tx.scan_in(&self.name, rng, limit).await
+	}
+
+	/// Retrieve a range of key-value pairs from this store in reverse order
+	pub async fn scanr<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error> {
+		tx.scanr_in(&self.name, rng, limit).await
+	}
+
+	/// Delete a range of keys from this store
+	pub async fn delr<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<(), Error> {
+		tx.delr_in(&self.name, rng, limit).await
+	}
+
+	/// Fetch several keys from this store in one batch
+	pub async fn getm<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		keys: Vec<Key>,
+	) -> Result<Vec<Option<Val>>, Error> {
+		tx.getm_in(&self.name, keys).await
+	}
+
+	/// Insert or update several key-value pairs in this store in one batch
+	pub async fn setm<B: Backend>(
+		&self,
+		tx: &mut Transaction<B>,
+		pairs: Vec<(Key, Val)>,
+	) -> Result<(), Error> {
+		tx.setm_in(&self.name, pairs).await
+	}
+}
+
+/// Compare two equal-length byte slices as little-endian unsigned integers
+fn le_cmp(a: &[u8], b: &[u8]) -> std::cmp::Ordering {
+	// Compare from the most-significant byte downwards
+	for i in (0..a.len()).rev() {
+		match a[i].cmp(&b[i]) {
+			std::cmp::Ordering::Equal => continue,
+			order => return order,
+		}
+	}
+	std::cmp::Ordering::Equal
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+	use super::*;
+	use crate::backend::MemoryBackend;
+	use std::future::Future;
+	use std::task::Context;
+	use std::task::Poll;
+	use std::task::RawWaker;
+	use std::task::RawWakerVTable;
+	use std::task::Waker;
+
+	/// Drive a future to completion on the current thread.
+	///
+	/// The in-memory backend never parks — every operation resolves on the
+	/// first poll — so a no-op waker is sufficient and avoids pulling in an
+	/// async runtime just to exercise the native code paths.
+	fn block_on<F: Future>(mut fut: F) -> F::Output {
+		fn noop(_: *const ()) {}
+		fn clone(_: *const ()) -> RawWaker {
+			RawWaker::new(std::ptr::null(), &VTABLE)
+		}
+		static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+		let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+		let mut cx = Context::from_waker(&waker);
+		// Safety: the future is not moved again before it is dropped
+		let mut fut = unsafe { std::pin::Pin::new_unchecked(&mut fut) };
+		loop {
+			if let Poll::Ready(val) = fut.as_mut().poll(&mut cx) {
+				return val;
+			}
+		}
+	}
+
+	/// Open a fresh writeable transaction over an empty in-memory store
+	fn begin() -> Transaction<MemoryBackend> {
+		Transaction::new(MemoryBackend::default(), true, "kv")
+	}
+
+	/// A [`Runner`] that begins transactions over a shared in-memory store,
+	/// standing in for the `wasm32`-only `Database` in native tests.
+	struct MemRunner {
+		base: MemoryBackend,
+		attempts: usize,
+	}
+
+	impl Runner for MemRunner {
+		type Backend = MemoryBackend;
+
+		fn attempts(&self) -> usize {
+			self.attempts
+		}
+
+		async fn begin_tx(
+			&self,
+			_stores: &[&str],
+			write: bool,
+		) -> Result<Transaction<MemoryBackend>, Error> {
+			Ok(Transaction::new(MemoryBackend::new(self.base.shared()), write, "kv"))
+		}
+	}
+
+	#[test]
+	fn atomic_add_carries_across_bytes() {
+		block_on(async {
+			let mut tx = begin();
+			// 0x00FF + 0x0001 = 0x0100 as a little-endian integer
+			tx.set(b"n".to_vec(), vec![0xFF, 0x00]).await.unwrap();
+			tx.atomic(b"n".to_vec(), vec![0x01, 0x00], AtomicOp::Add).await.unwrap();
+			assert_eq!(tx.get(b"n".to_vec()).await.unwrap(), Some(vec![0x00, 0x01]));
+		});
+	}
+
+	#[test]
+	fn atomic_add_missing_key_is_zero() {
+		block_on(async {
+			let mut tx = begin();
+			// A missing key starts from all-zero bytes
+			tx.atomic(b"n".to_vec(), vec![0x05], AtomicOp::Add).await.unwrap();
+			assert_eq!(tx.get(b"n".to_vec()).await.unwrap(), Some(vec![0x05]));
+		});
+	}
+
+	#[test]
+	fn atomic_min_max_use_integer_order() {
+		block_on(async {
+			let mut tx = begin();
+			// Bytes [0x00, 0x01] are the integer 0x0100 in little-endian order,
+			// larger than [0x01, 0x00] (0x0001) though lexicographically smaller
+			tx.set(b"k".to_vec(), vec![0x00, 0x01]).await.unwrap();
+			tx.atomic(b"k".to_vec(), vec![0x01, 0x00], AtomicOp::Min).await.unwrap();
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), Some(vec![0x01, 0x00]));
+			tx.atomic(b"k".to_vec(), vec![0x00, 0x01], AtomicOp::Max).await.unwrap();
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), Some(vec![0x00, 0x01]));
+		});
+	}
+
+	#[test]
+	fn atomic_bitwise_operators() {
+		block_on(async {
+			let mut tx = begin();
+			tx.set(b"b".to_vec(), vec![0b1100]).await.unwrap();
+			tx.atomic(b"b".to_vec(), vec![0b1010], AtomicOp::BitAnd).await.unwrap();
+			assert_eq!(tx.get(b"b".to_vec()).await.unwrap(), Some(vec![0b1000]));
+			tx.atomic(b"b".to_vec(), vec![0b0011], AtomicOp::BitOr).await.unwrap();
+			assert_eq!(tx.get(b"b".to_vec()).await.unwrap(), Some(vec![0b1011]));
+			tx.atomic(b"b".to_vec(), vec![0b1111], AtomicOp::BitXor).await.unwrap();
+			assert_eq!(tx.get(b"b".to_vec()).await.unwrap(), Some(vec![0b0100]));
+		});
+	}
+
+	#[test]
+	fn le_cmp_orders_by_significance() {
+		// The high byte dominates regardless of the low byte
+		assert_eq!(le_cmp(&[0xFF, 0x00], &[0x00, 0x01]), std::cmp::Ordering::Less);
+		assert_eq!(le_cmp(&[0x02, 0x01], &[0x01, 0x01]), std::cmp::Ordering::Greater);
+		assert_eq!(le_cmp(&[0x07, 0x07], &[0x07, 0x07]), std::cmp::Ordering::Equal);
+	}
+
+	#[test]
+	fn savepoint_rolls_back_writes_and_deletes() {
+		block_on(async {
+			let mut tx = begin();
+			tx.set(b"a".to_vec(), b"1".to_vec()).await.unwrap();
+			tx.set(b"b".to_vec(), b"2".to_vec()).await.unwrap();
+			tx.set_savepoint().await.unwrap();
+			// Mutate existing keys and insert a new one after the savepoint
+			tx.set(b"a".to_vec(), b"changed".to_vec()).await.unwrap();
+			tx.del(b"b".to_vec()).await.unwrap();
+			tx.set(b"c".to_vec(), b"3".to_vec()).await.unwrap();
+			tx.rollback_to_savepoint().await.unwrap();
+			// The pre-savepoint state is restored exactly
+			assert_eq!(tx.get(b"a".to_vec()).await.unwrap(), Some(b"1".to_vec()));
+			assert_eq!(tx.get(b"b".to_vec()).await.unwrap(), Some(b"2".to_vec()));
+			assert_eq!(tx.get(b"c".to_vec()).await.unwrap(), None);
+		});
+	}
+
+	#[test]
+	fn putc_and_delc_honour_the_check_value() {
+		block_on(async {
+			let mut tx = begin();
+			// putc inserts only when the key is absent
+			tx.putc(b"k".to_vec(), b"v".to_vec(), None).await.unwrap();
+			assert!(matches!(
+				tx.putc(b"k".to_vec(), b"w".to_vec(), None).await,
+				Err(Error::ValNotExpectedValue)
+			));
+			// putc overwrites only when the check value matches
+			tx.putc(b"k".to_vec(), b"w".to_vec(), Some(b"v".to_vec())).await.unwrap();
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), Some(b"w".to_vec()));
+			// delc removes only when the check value matches
+			assert!(matches!(
+				tx.delc(b"k".to_vec(), Some(b"v".to_vec())).await,
+				Err(Error::ValNotExpectedValue)
+			));
+			tx.delc(b"k".to_vec(), Some(b"w".to_vec())).await.unwrap();
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), None);
+		});
+	}
+
+	#[test]
+	fn reverse_scan_returns_descending_keys() {
+		block_on(async {
+			let mut tx = begin();
+			for k in [b"a", b"b", b"c", b"d"] {
+				tx.set(k.to_vec(), b"v".to_vec()).await.unwrap();
+			}
+			let fwd = tx.keys(b"a".to_vec()..b"e".to_vec(), 10).await.unwrap();
+			assert_eq!(fwd, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec(), b"d".to_vec()]);
+			// A reverse scan yields the same range in descending order
+			let rev = tx.keysr(b"a".to_vec()..b"e".to_vec(), 2).await.unwrap();
+			assert_eq!(rev, vec![b"d".to_vec(), b"c".to_vec()]);
+		});
+	}
+
+	#[test]
+	fn runner_commits_on_ok() {
+		block_on(async {
+			let runner = MemRunner {
+				base: MemoryBackend::default(),
+				attempts: 5,
+			};
+			// The closure's write should be durably committed on success
+			let out = runner
+				.run(&["kv"], true, async |tx| {
+					tx.set(b"k".to_vec(), b"v".to_vec()).await?;
+					Ok(7)
+				})
+				.await
+				.unwrap();
+			assert_eq!(out, 7);
+			// A fresh transaction observes the committed value
+			let mut tx = Transaction::new(MemoryBackend::new(runner.base.shared()), false, "kv");
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), Some(b"v".to_vec()));
+		});
+	}
+
+	#[test]
+	fn runner_cancels_on_err() {
+		block_on(async {
+			let runner = MemRunner {
+				base: MemoryBackend::default(),
+				attempts: 5,
+			};
+			// The closure writes, then returns an error, so nothing commits
+			let res: Result<(), Error> = runner
+				.run(&["kv"], true, async |tx| {
+					tx.set(b"k".to_vec(), b"v".to_vec()).await?;
+					Err(Error::TxError)
+				})
+				.await;
+			assert!(matches!(res, Err(Error::TxError)));
+			// The cancelled write must not be visible afterwards
+			let mut tx = Transaction::new(MemoryBackend::new(runner.base.shared()), false, "kv");
+			assert_eq!(tx.get(b"k".to_vec()).await.unwrap(), None);
+		});
+	}
+
+	#[test]
+	fn commit_merges_without_clobbering_concurrent_writes() {
+		block_on(async {
+			// Two transactions open against the same shared datastore
+			let first = MemoryBackend::default();
+			let second = MemoryBackend::new(first.shared());
+			let mut t1 = Transaction::new(first, true, "kv");
+			let mut t2 = Transaction::new(second, true, "kv");
+			// Each writes a different key, then both commit
+			t1.set(b"a".to_vec(), b"1".to_vec()).await.unwrap();
+			t2.set(b"b".to_vec(), b"2".to_vec()).await.unwrap();
+			t1.commit().await.unwrap();
+			t2.commit().await.unwrap();
+			// The later commit must not discard the earlier one's key
+			let mut t3 = Transaction::new(MemoryBackend::new(t2.backend.shared()), false, "kv");
+			assert_eq!(t3.get(b"a".to_vec()).await.unwrap(), Some(b"1".to_vec()));
+			assert_eq!(t3.get(b"b".to_vec()).await.unwrap(), Some(b"2".to_vec()));
+		});
 	}
 }